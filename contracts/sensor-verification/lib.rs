@@ -1,5 +1,6 @@
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::ed25519_program;
+use anchor_lang::solana_program::sysvar::instructions::load_instruction_at_checked;
 
 declare_id!("DePiN1111111111111111111111111111111111111");
 
@@ -48,6 +49,7 @@ pub mod sensor_verification {
         // Verify signature using ed25519
         let message = create_message(&sensor_type, timestamp, &data, &sensor_account.device_id);
         verify_ed25519_signature(
+            &ctx.accounts.instructions,
             &signature,
             &ctx.accounts.authority.key().to_bytes(),
             &message,
@@ -71,6 +73,118 @@ pub mod sensor_verification {
         Ok(())
     }
 
+    /// Submit and verify many proofs atomically in a single instruction
+    pub fn submit_proofs_batch(
+        ctx: Context<SubmitProofsBatch>,
+        proofs: Vec<ProofSubmission>,
+    ) -> Result<()> {
+        require!(!proofs.is_empty(), ErrorCode::InvalidProofCount);
+        require!(
+            proofs.len() == ctx.remaining_accounts.len(),
+            ErrorCode::ProofAccountCountMismatch
+        );
+
+        let clock = Clock::get()?;
+        let rent = Rent::get()?;
+
+        require!(
+            ctx.accounts.sensor_account.is_active,
+            ErrorCode::AccountInactive
+        );
+
+        let device_id = ctx.accounts.sensor_account.device_id.clone();
+        let authority_key = ctx.accounts.authority.key();
+
+        for (proof, proof_account_info) in proofs.iter().zip(ctx.remaining_accounts.iter()) {
+            require!(
+                proof.timestamp <= clock.unix_timestamp,
+                ErrorCode::InvalidTimestamp
+            );
+
+            let message =
+                create_message(&proof.sensor_type, proof.timestamp, &proof.data, &device_id);
+            verify_ed25519_signature(
+                &ctx.accounts.instructions,
+                &proof.signature,
+                &authority_key.to_bytes(),
+                &message,
+            )?;
+
+            let space =
+                8 + 32 + (4 + proof.sensor_type.len()) + 8 + (4 + proof.data.len()) + 64 + 32 + 8;
+            let lamports = rent.minimum_balance(space);
+
+            anchor_lang::system_program::create_account(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::CreateAccount {
+                        from: ctx.accounts.authority.to_account_info(),
+                        to: proof_account_info.clone(),
+                    },
+                ),
+                lamports,
+                space as u64,
+                ctx.program_id,
+            )?;
+
+            let proof_data = ProofAccount {
+                sensor_account: ctx.accounts.sensor_account.key(),
+                sensor_type: proof.sensor_type.clone(),
+                timestamp: proof.timestamp,
+                data: proof.data.clone(),
+                signature: proof.signature,
+                verifier: authority_key,
+                verified_at: clock.unix_timestamp,
+            };
+
+            let mut account_data = proof_account_info.try_borrow_mut_data()?;
+            account_data[..8].copy_from_slice(&ProofAccount::DISCRIMINATOR);
+            proof_data.serialize(&mut &mut account_data[8..])?;
+        }
+
+        let sensor_account = &mut ctx.accounts.sensor_account;
+        sensor_account.proof_count += proofs.len() as u64;
+        sensor_account.last_proof_timestamp = proofs
+            .iter()
+            .map(|p| p.timestamp)
+            .fold(sensor_account.last_proof_timestamp, i64::max);
+        sensor_account.total_proofs_verified += proofs.len() as u64;
+
+        msg!("Batch of {} proofs submitted and verified atomically", proofs.len());
+        Ok(())
+    }
+
+    /// Grow an existing proof account to append more reading data
+    pub fn append_proof_data(ctx: Context<AppendProofData>, additional_data: Vec<u8>) -> Result<()> {
+        let proof_account_info = ctx.accounts.proof_account.to_account_info();
+
+        let old_len = proof_account_info.data_len();
+        let new_len = old_len + additional_data.len();
+
+        let rent = Rent::get()?;
+        let additional_rent =
+            rent.minimum_balance(new_len).saturating_sub(proof_account_info.lamports());
+
+        if additional_rent > 0 {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: ctx.accounts.authority.to_account_info(),
+                        to: proof_account_info.clone(),
+                    },
+                ),
+                additional_rent,
+            )?;
+        }
+
+        proof_account_info.realloc(new_len, true)?;
+        ctx.accounts.proof_account.data.extend_from_slice(&additional_data);
+
+        msg!("Proof account grown from {} to {} bytes", old_len, new_len);
+        Ok(())
+    }
+
     /// Submit a batch of proofs with Merkle root verification
     pub fn submit_batch_proof(
         ctx: Context<SubmitBatchProof>,
@@ -113,25 +227,29 @@ pub mod sensor_verification {
         Ok(())
     }
 
-    /// Verify a single proof against a batch Merkle root
-    pub fn verify_merkle_proof(
-        ctx: Context<VerifyMerkleProof>,
-        proof_hash: [u8; 32],
+    /// Verify a specific sensor reading's inclusion in a batch Merkle root
+    pub fn verify_sensor_reading_inclusion(
+        ctx: Context<VerifySensorReadingInclusion>,
+        sensor_type: String,
+        timestamp: i64,
+        data: Vec<u8>,
         merkle_path: Vec<[u8; 32]>,
         index: u32,
     ) -> Result<()> {
+        let sensor_account = &ctx.accounts.sensor_account;
         let batch_account = &ctx.accounts.batch_account;
 
-        // Compute Merkle root from path
-        let computed_root = compute_merkle_root(proof_hash, &merkle_path, index);
+        let message = create_message(&sensor_type, timestamp, &data, &sensor_account.device_id);
+        let leaf = hash_leaf(&message);
+
+        let computed_root = compute_merkle_root(leaf, &merkle_path, index);
 
-        // Verify it matches the stored root
         require!(
             computed_root == batch_account.merkle_root,
             ErrorCode::InvalidMerkleProof
         );
 
-        msg!("Merkle proof verified successfully");
+        msg!("Sensor reading inclusion verified successfully");
         Ok(())
     }
 
@@ -151,15 +269,19 @@ pub mod sensor_verification {
         Ok(())
     }
 
-    /// Get sensor account statistics
+    /// Get sensor account statistics, also written to the return-data buffer
     pub fn get_sensor_stats(ctx: Context<GetSensorStats>) -> Result<SensorStats> {
         let sensor_account = &ctx.accounts.sensor_account;
-        
-        Ok(SensorStats {
+
+        let stats = SensorStats {
             total_proofs: sensor_account.total_proofs_verified,
             last_proof_timestamp: sensor_account.last_proof_timestamp,
             is_active: sensor_account.is_active,
-        })
+        };
+
+        anchor_lang::solana_program::program::set_return_data(&stats.try_to_vec()?);
+
+        Ok(stats)
     }
 }
 
@@ -185,6 +307,15 @@ pub struct ProofAccount {
     pub verified_at: i64,
 }
 
+/// A single proof tuple carried in a `submit_proofs_batch` call.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ProofSubmission {
+    pub sensor_type: String,
+    pub timestamp: i64,
+    pub data: Vec<u8>,
+    pub signature: [u8; 64],
+}
+
 #[account]
 pub struct BatchProofAccount {
     pub sensor_account: Pubkey,
@@ -217,7 +348,40 @@ pub struct SubmitProof<'info> {
     #[account(
         init,
         payer = authority,
-        space = 8 + 32 + 64 + 8 + 256 + 64 + 32 + 8
+        // Exact size for this reading instead of a fixed `data` ceiling:
+        // discriminator + sensor_account pubkey + length-prefixed
+        // sensor_type + timestamp + length-prefixed data + signature +
+        // verifier pubkey + verified_at.
+        space = 8 + 32 + (4 + sensor_type.len()) + 8 + (4 + data.len()) + 64 + 32 + 8
+    )]
+    pub proof_account: Account<'info, ProofAccount>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: validated by `load_instruction_at_checked` against the sysvar ID
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SubmitProofsBatch<'info> {
+    #[account(mut)]
+    pub sensor_account: Account<'info, SensorAccount>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: validated by `load_instruction_at_checked` against the sysvar ID
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: AccountInfo<'info>,
+    // One uninitialized, system-owned `ProofAccount` keypair per entry in
+    // `proofs`, in the same order, passed as remaining accounts.
+}
+
+#[derive(Accounts)]
+pub struct AppendProofData<'info> {
+    #[account(
+        mut,
+        constraint = proof_account.verifier == authority.key() @ ErrorCode::Unauthorized
     )]
     pub proof_account: Account<'info, ProofAccount>,
     #[account(mut)]
@@ -241,7 +405,9 @@ pub struct SubmitBatchProof<'info> {
 }
 
 #[derive(Accounts)]
-pub struct VerifyMerkleProof<'info> {
+pub struct VerifySensorReadingInclusion<'info> {
+    pub sensor_account: Account<'info, SensorAccount>,
+    #[account(has_one = sensor_account)]
     pub batch_account: Account<'info, BatchProofAccount>,
 }
 
@@ -281,20 +447,218 @@ fn create_message(sensor_type: &str, timestamp: i64, data: &[u8], device_id: &st
     message
 }
 
-fn verify_ed25519_signature(
+/// Confirms that an ed25519-program instruction anywhere in this transaction
+/// verified `signature` by `public_key` over exactly `message`
+fn verify_ed25519_signature<'info>(
+    instructions: &AccountInfo<'info>,
     signature: &[u8; 64],
     public_key: &[u8; 32],
     message: &[u8],
 ) -> Result<()> {
-    // In production, use the ed25519_program for verification
-    // This is a simplified version for demonstration
-    msg!("Verifying signature...");
-    
-    // The actual verification would happen via CPI to ed25519_program
-    // For now, we'll assume verification passes
-    // In production: use ed25519_program::verify
-    
-    Ok(())
+    let mut index: u16 = 0;
+    while let Ok(ix) = load_instruction_at_checked(index as usize, instructions) {
+        if ix.program_id == ed25519_program::ID
+            && ed25519_instruction_covers(&ix.data, index, instructions, signature, public_key, message)
+        {
+            return Ok(());
+        }
+        index += 1;
+    }
+
+    err!(ErrorCode::InvalidSignature)
+}
+
+/// One 14-byte record from an `ed25519_program` instruction's data, as laid
+/// out by `Ed25519SignatureOffsets` in the Solana runtime.
+#[derive(Debug, PartialEq, Eq)]
+struct Ed25519SignatureOffsets {
+    signature_offset: usize,
+    signature_instruction_index: u16,
+    public_key_offset: usize,
+    public_key_instruction_index: u16,
+    message_data_offset: usize,
+    message_data_size: usize,
+    message_instruction_index: u16,
+}
+
+const ED25519_OFFSETS_LEN: usize = 14;
+
+/// Parses an `ed25519_program` instruction's data (one byte signature count,
+/// one padding byte, then one `Ed25519SignatureOffsets` record per
+/// signature). Returns `None` if `data` is truncated partway through.
+fn parse_ed25519_signature_offsets(data: &[u8]) -> Option<Vec<Ed25519SignatureOffsets>> {
+    let &num_signatures = data.first()?;
+    let mut cursor = 2usize;
+    let mut offsets = Vec::with_capacity(num_signatures as usize);
+
+    for _ in 0..num_signatures {
+        let record = data.get(cursor..cursor + ED25519_OFFSETS_LEN)?;
+        cursor += ED25519_OFFSETS_LEN;
+
+        offsets.push(Ed25519SignatureOffsets {
+            signature_offset: u16::from_le_bytes([record[0], record[1]]) as usize,
+            signature_instruction_index: u16::from_le_bytes([record[2], record[3]]),
+            public_key_offset: u16::from_le_bytes([record[4], record[5]]) as usize,
+            public_key_instruction_index: u16::from_le_bytes([record[6], record[7]]),
+            message_data_offset: u16::from_le_bytes([record[8], record[9]]) as usize,
+            message_data_size: u16::from_le_bytes([record[10], record[11]]) as usize,
+            message_instruction_index: u16::from_le_bytes([record[12], record[13]]),
+        });
+    }
+
+    Some(offsets)
+}
+
+/// Resolves an offsets record's `*_instruction_index` field, where
+/// `u16::MAX` means "this same ed25519 instruction".
+fn resolve_instruction_index(instruction_index: u16, self_index: u16) -> u16 {
+    if instruction_index == u16::MAX {
+        self_index
+    } else {
+        instruction_index
+    }
+}
+
+/// Checks whether any offsets record in `data` points at exactly
+/// `signature`, `public_key`, and `message` in the referenced instructions.
+fn ed25519_instruction_covers<'info>(
+    data: &[u8],
+    self_index: u16,
+    instructions: &AccountInfo<'info>,
+    signature: &[u8; 64],
+    public_key: &[u8; 32],
+    message: &[u8],
+) -> bool {
+    let Some(offsets) = parse_ed25519_signature_offsets(data) else {
+        return false;
+    };
+
+    let resolve = |instruction_index: u16| -> Option<Vec<u8>> {
+        let target = resolve_instruction_index(instruction_index, self_index);
+        load_instruction_at_checked(target as usize, instructions)
+            .ok()
+            .map(|ix| ix.data)
+    };
+
+    offsets.into_iter().any(|record| {
+        let Some(sig_data) = resolve(record.signature_instruction_index) else {
+            return false;
+        };
+        let Some(pk_data) = resolve(record.public_key_instruction_index) else {
+            return false;
+        };
+        let Some(msg_data) = resolve(record.message_instruction_index) else {
+            return false;
+        };
+
+        sig_data
+            .get(record.signature_offset..record.signature_offset + 64)
+            .map_or(false, |s| s == signature)
+            && pk_data
+                .get(record.public_key_offset..record.public_key_offset + 32)
+                .map_or(false, |p| p == public_key)
+            && msg_data
+                .get(
+                    record.message_data_offset
+                        ..record.message_data_offset + record.message_data_size,
+                )
+                .map_or(false, |m| m == message)
+    })
+}
+
+#[cfg(test)]
+mod ed25519_offsets_tests {
+    use super::*;
+
+    fn offsets_record(
+        signature_offset: u16,
+        signature_instruction_index: u16,
+        public_key_offset: u16,
+        public_key_instruction_index: u16,
+        message_data_offset: u16,
+        message_data_size: u16,
+        message_instruction_index: u16,
+    ) -> Vec<u8> {
+        let mut record = Vec::with_capacity(ED25519_OFFSETS_LEN);
+        record.extend_from_slice(&signature_offset.to_le_bytes());
+        record.extend_from_slice(&signature_instruction_index.to_le_bytes());
+        record.extend_from_slice(&public_key_offset.to_le_bytes());
+        record.extend_from_slice(&public_key_instruction_index.to_le_bytes());
+        record.extend_from_slice(&message_data_offset.to_le_bytes());
+        record.extend_from_slice(&message_data_size.to_le_bytes());
+        record.extend_from_slice(&message_instruction_index.to_le_bytes());
+        record
+    }
+
+    #[test]
+    fn parses_a_single_signature_record() {
+        let mut data = vec![1u8, 0u8];
+        data.extend(offsets_record(16, 0xFFFF, 80, 0xFFFF, 112, 32, 0xFFFF));
+
+        let offsets = parse_ed25519_signature_offsets(&data).expect("should parse");
+        assert_eq!(offsets.len(), 1);
+        assert_eq!(
+            offsets[0],
+            Ed25519SignatureOffsets {
+                signature_offset: 16,
+                signature_instruction_index: 0xFFFF,
+                public_key_offset: 80,
+                public_key_instruction_index: 0xFFFF,
+                message_data_offset: 112,
+                message_data_size: 32,
+                message_instruction_index: 0xFFFF,
+            }
+        );
+    }
+
+    #[test]
+    fn parses_zero_signatures() {
+        let data = vec![0u8, 0u8];
+        assert_eq!(parse_ed25519_signature_offsets(&data), Some(Vec::new()));
+    }
+
+    #[test]
+    fn rejects_empty_data() {
+        assert_eq!(parse_ed25519_signature_offsets(&[]), None);
+    }
+
+    #[test]
+    fn rejects_a_record_truncated_partway_through() {
+        let mut data = vec![1u8, 0u8];
+        data.extend_from_slice(&[0u8; ED25519_OFFSETS_LEN - 1]);
+        assert_eq!(parse_ed25519_signature_offsets(&data), None);
+    }
+
+    #[test]
+    fn rejects_a_missing_second_record() {
+        let mut data = vec![2u8, 0u8];
+        data.extend(offsets_record(0, 0, 0, 0, 0, 0, 0));
+        assert_eq!(parse_ed25519_signature_offsets(&data), None);
+    }
+
+    #[test]
+    fn resolves_self_reference_to_the_current_instruction() {
+        assert_eq!(resolve_instruction_index(0xFFFF, 3), 3);
+    }
+
+    #[test]
+    fn resolves_an_explicit_instruction_index_unchanged() {
+        assert_eq!(resolve_instruction_index(2, 3), 2);
+    }
+}
+
+// Domain separation tags for the Merkle tree, to stop a second-preimage
+// attack where an internal node (hash of two 32-byte children) is passed
+// off as a leaf (hash of arbitrary-length reading data), or vice versa.
+const LEAF_DOMAIN_TAG: u8 = 0x00;
+const NODE_DOMAIN_TAG: u8 = 0x01;
+
+/// Hashes a sensor reading's message into a domain-separated Merkle leaf.
+fn hash_leaf(message: &[u8]) -> [u8; 32] {
+    let mut hasher = anchor_lang::solana_program::keccak::Hasher::default();
+    hasher.hash(&[LEAF_DOMAIN_TAG]);
+    hasher.hash(message);
+    hasher.result().to_bytes()
 }
 
 fn compute_merkle_root(leaf: [u8; 32], path: &[[u8; 32]], index: u32) -> [u8; 32] {
@@ -303,7 +667,8 @@ fn compute_merkle_root(leaf: [u8; 32], path: &[[u8; 32]], index: u32) -> [u8; 32
 
     for sibling in path {
         let mut hasher = anchor_lang::solana_program::keccak::Hasher::default();
-        
+        hasher.hash(&[NODE_DOMAIN_TAG]);
+
         if idx % 2 == 0 {
             // Current is left, sibling is right
             hasher.hash(&current);
@@ -321,6 +686,87 @@ fn compute_merkle_root(leaf: [u8; 32], path: &[[u8; 32]], index: u32) -> [u8; 32
     current
 }
 
+#[cfg(test)]
+mod merkle_domain_separation_tests {
+    use super::*;
+    use anchor_lang::solana_program::keccak;
+
+    #[test]
+    fn leaf_hash_includes_domain_tag() {
+        let message = b"sensor-reading".to_vec();
+        let leaf = hash_leaf(&message);
+        let undomained = keccak::hash(&message).to_bytes();
+
+        assert_ne!(
+            leaf, undomained,
+            "leaf hash must differ from an untagged hash of the same message"
+        );
+    }
+
+    #[test]
+    fn node_hash_includes_domain_tag() {
+        let left = [1u8; 32];
+        let right = [2u8; 32];
+        let root = compute_merkle_root(left, &[right], 0);
+
+        let mut concatenated = Vec::new();
+        concatenated.extend_from_slice(&left);
+        concatenated.extend_from_slice(&right);
+        let undomained = keccak::hash(&concatenated).to_bytes();
+
+        assert_ne!(
+            root, undomained,
+            "internal node hash must differ from an untagged hash of the same children"
+        );
+    }
+
+    #[test]
+    fn leaf_and_node_digests_differ_for_the_same_bytes() {
+        let left = [3u8; 32];
+        let right = [4u8; 32];
+
+        let mut concatenated = Vec::new();
+        concatenated.extend_from_slice(&left);
+        concatenated.extend_from_slice(&right);
+
+        let leaf_digest = hash_leaf(&concatenated);
+        let node_digest = compute_merkle_root(left, &[right], 0);
+
+        assert_ne!(
+            leaf_digest, node_digest,
+            "an internal node must not be replayable as a leaf of the same bytes"
+        );
+    }
+
+    #[test]
+    fn orders_children_left_when_index_is_even() {
+        let current = [5u8; 32];
+        let sibling = [6u8; 32];
+
+        let mut hasher = keccak::Hasher::default();
+        hasher.hash(&[NODE_DOMAIN_TAG]);
+        hasher.hash(&current);
+        hasher.hash(&sibling);
+        let expected = hasher.result().to_bytes();
+
+        assert_eq!(compute_merkle_root(current, &[sibling], 0), expected);
+    }
+
+    #[test]
+    fn orders_children_right_when_index_is_odd() {
+        let current = [7u8; 32];
+        let sibling = [8u8; 32];
+
+        let mut hasher = keccak::Hasher::default();
+        hasher.hash(&[NODE_DOMAIN_TAG]);
+        hasher.hash(&sibling);
+        hasher.hash(&current);
+        let expected = hasher.result().to_bytes();
+
+        assert_eq!(compute_merkle_root(current, &[sibling], 1), expected);
+    }
+}
+
 // Error codes
 #[error_code]
 pub enum ErrorCode {
@@ -336,4 +782,8 @@ pub enum ErrorCode {
     InvalidTimestampRange,
     #[msg("Invalid Merkle proof")]
     InvalidMerkleProof,
+    #[msg("Number of proof accounts does not match number of proofs")]
+    ProofAccountCountMismatch,
+    #[msg("Only the original proof submitter may modify this proof account")]
+    Unauthorized,
 }
\ No newline at end of file